@@ -1,3 +1,5 @@
+mod disassembler;
+
 pub const SCREEN_WIDTH: usize = 64;
 pub const SCREEN_HEIGHT: usize = 32;
 
@@ -6,6 +8,7 @@ const NUM_REGS: usize = 16;
 const STACK_SIZE: usize = 16;
 const NUM_KEYS: usize = 16;
 
+#[derive(Clone)]
 pub struct Emu {
     pc: u16,
     ram: [u8; RAM_SIZE],
@@ -40,6 +43,57 @@ impl Emu {
             st: 0,
         }
     }
+
+    // All fields are plain, fixed-size arrays/scalars, so a snapshot is just
+    // a clone of the whole struct; used by the desktop frontend's rewind buffer.
+    pub fn snapshot(&self) -> Self {
+        self.clone()
+    }
+
+    pub fn restore(&mut self, snapshot: &Self) {
+        *self = snapshot.clone();
+    }
+
+    pub fn get_pc(&self) -> u16 {
+        self.pc
+    }
+
+    pub fn get_i_reg(&self) -> u16 {
+        self.i_reg
+    }
+
+    pub fn get_sp(&self) -> u16 {
+        self.sp
+    }
+
+    pub fn get_dt(&self) -> u8 {
+        self.dt
+    }
+
+    pub fn get_v_reg(&self) -> &[u8; NUM_REGS] {
+        &self.v_reg
+    }
+
+    pub fn get_stack(&self) -> &[u16; STACK_SIZE] {
+        &self.stack
+    }
+
+    // Decodes the instruction at `addr` into a mnemonic, e.g. "LD I, 0x200".
+    pub fn disassemble(&self, addr: u16) -> String {
+        disassembler::decode(self.opcode_at(addr))
+    }
+
+    // Decodes `count` consecutive instructions starting at `addr`, for
+    // rendering a scrolling disassembly window around the current `pc`.
+    pub fn disassemble_range(&self, addr: u16, count: usize) -> Vec<(u16, String)> {
+        disassembler::disassemble_range(&self.ram, addr, count)
+    }
+
+    fn opcode_at(&self, addr: u16) -> u16 {
+        let hi = *self.ram.get(addr as usize).unwrap_or(&0) as u16;
+        let lo = *self.ram.get(addr as usize + 1).unwrap_or(&0) as u16;
+        (hi << 8) | lo
+    }
 }
 
 // stack num is not defined in the spec but 16 is the de-fact standard among emu develolpers
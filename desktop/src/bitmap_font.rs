@@ -0,0 +1,93 @@
+// A tiny built-in 3x5 pixel font, so the debug overlay (`Tab`) has readable
+// text out of the box without requiring a user-supplied TTF file. Only
+// upper-case letters, digits, and the punctuation the overlay actually emits
+// are defined; `draw_text` upper-cases its input to route everything through
+// this set.
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+pub const GLYPH_COLS: u32 = 3;
+pub const GLYPH_ROWS: u32 = 5;
+
+// Each row is a 3-bit mask, MSB = leftmost column.
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b111, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b111, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b111],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b101, 0b111, 0b111, 0b111, 0b101],
+        'O' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        'P' => [0b111, 0b101, 0b111, 0b100, 0b100],
+        'Q' => [0b111, 0b101, 0b101, 0b111, 0b001],
+        'R' => [0b111, 0b101, 0b111, 0b110, 0b101],
+        'S' => [0b011, 0b100, 0b111, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        '[' => [0b110, 0b100, 0b100, 0b100, 0b110],
+        ']' => [0b011, 0b001, 0b001, 0b001, 0b011],
+        '>' => [0b100, 0b010, 0b001, 0b010, 0b100],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        _ => [0b000, 0b000, 0b000, 0b000, 0b000],
+    }
+}
+
+// Draws `text` as a single line of blocky pixels, each glyph pixel a
+// `pixel_size` px square, top-left anchored at (x, y).
+pub fn draw_text(canvas: &mut Canvas<Window>, x: i32, y: i32, text: &str, color: Color, pixel_size: u32) {
+    canvas.set_draw_color(color);
+    let advance = ((GLYPH_COLS + 1) * pixel_size) as i32;
+    for (i, c) in text.to_ascii_uppercase().chars().enumerate() {
+        let cell_x = x + i as i32 * advance;
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for col in 0..GLYPH_COLS {
+                if bits & (1 << (GLYPH_COLS - 1 - col)) != 0 {
+                    let px = cell_x + (col * pixel_size) as i32;
+                    let py = y + row as i32 * pixel_size as i32;
+                    canvas
+                        .fill_rect(Rect::new(px, py, pixel_size, pixel_size))
+                        .unwrap();
+                }
+            }
+        }
+    }
+}
+
+// Total pixel width of `text` when drawn with `draw_text` at `pixel_size`.
+pub fn text_width(text: &str, pixel_size: u32) -> u32 {
+    text.len() as u32 * (GLYPH_COLS + 1) * pixel_size
+}
+
+// Total pixel height of one line drawn with `draw_text` at `pixel_size`.
+pub fn text_height(pixel_size: u32) -> u32 {
+    GLYPH_ROWS * pixel_size
+}
@@ -1,48 +1,417 @@
+mod bitmap_font;
+mod recorder;
+
 use chip8_core::*;
+use recorder::{Recorder, RecorderConfig};
 
+use std::collections::{HashMap, VecDeque};
 use std::env;
 use std::fs::File;
 use std::io::Read;
 use std::thread::sleep;
 use std::time::{Duration, Instant};
 
-use sdl2::audio::{AudioQueue, AudioSpecDesired};
-use sdl2::event::Event;
+use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
+use sdl2::controller::{Axis, Button};
+use sdl2::event::{Event, WindowEvent};
 use sdl2::keyboard::Keycode;
-use sdl2::pixels::{self, Color};
+use sdl2::pixels::{Color, PixelFormatEnum};
 use sdl2::rect::Rect;
-use sdl2::render::Canvas;
-use sdl2::video::Window;
+use sdl2::render::{BlendMode, Canvas, Texture, TextureCreator, TextureQuery};
+use sdl2::ttf::Font;
+use sdl2::video::{Window, WindowContext};
+
+// Analog stick displacement (of i16::MAX) below which axis motion is
+// ignored, so resting sticks don't register as held D-pad directions.
+const AXIS_DEAD_ZONE: i16 = 8_000;
+
+// Default D-pad/face-button/shoulder mapping onto the CHIP-8 hex keypad.
+// Overridable via a "ButtonName=HexDigit" config file (one mapping per line).
+fn default_controller_mapping() -> HashMap<Button, usize> {
+    let mut mapping = HashMap::new();
+    mapping.insert(Button::DPadUp, 0x2);
+    mapping.insert(Button::DPadDown, 0x8);
+    mapping.insert(Button::DPadLeft, 0x4);
+    mapping.insert(Button::DPadRight, 0x6);
+    mapping.insert(Button::A, 0x5);
+    mapping.insert(Button::B, 0x0);
+    mapping.insert(Button::X, 0x7);
+    mapping.insert(Button::Y, 0x9);
+    mapping.insert(Button::LeftShoulder, 0x3);
+    mapping.insert(Button::RightShoulder, 0xC);
+    mapping.insert(Button::Start, 0xA);
+    mapping.insert(Button::Back, 0xB);
+    mapping
+}
+
+// Parses a "ButtonName=HexDigit" config file on top of the default mapping.
+// A missing/unreadable file leaves the defaults as they are, and lines that
+// don't parse are skipped.
+fn load_controller_mapping(path: &str) -> HashMap<Button, usize> {
+    let mut mapping = default_controller_mapping();
+    if let Ok(contents) = std::fs::read_to_string(path) {
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((name, digit)) = line.split_once('=') {
+                if let (Some(button), Ok(digit)) =
+                    (Button::from_string(name.trim()), u8::from_str_radix(digit.trim(), 16))
+                {
+                    mapping.insert(button, digit as usize);
+                }
+            }
+        }
+    }
+    mapping
+}
+
+// Translates a continuous analog axis reading into the discrete D-pad button
+// it shadows (past the dead zone), so callers only ever deal in key-down/up.
+fn axis_to_button(axis: Axis, value: i16) -> Option<Button> {
+    let direction = if value > AXIS_DEAD_ZONE {
+        1
+    } else if value < -AXIS_DEAD_ZONE {
+        -1
+    } else {
+        0
+    };
+    match (axis, direction) {
+        (Axis::LeftX, 1) => Some(Button::DPadRight),
+        (Axis::LeftX, -1) => Some(Button::DPadLeft),
+        (Axis::LeftY, 1) => Some(Button::DPadDown),
+        (Axis::LeftY, -1) => Some(Button::DPadUp),
+        _ => None,
+    }
+}
+
+// Default "times" scale used when no --scale option is given, and as the
+// starting window size before the user resizes it.
+const DEFAULT_SCALE: f32 = 15.0;
+
+// Default output resolution for --record when no ":WxH" suffix is given.
+const DEFAULT_RECORD_WIDTH: u32 = 640;
+const DEFAULT_RECORD_HEIGHT: u32 = 320;
 
-const SCALE: u32 = 15;
-const WINDOW_WIDTH: u32 = (SCREEN_WIDTH as u32) * SCALE;
-const WINDOW_HEIGHT: u32 = (SCREEN_HEIGHT as u32) * SCALE;
+// Pulls "<flag> <value>" out of the argument list in place, leaving the rest
+// of the argument list untouched, and returns the value if the flag was
+// present. Exits with an error instead of panicking when the flag is passed
+// without a following value.
+fn extract_value_flag(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let flag_pos = args.iter().position(|a| a == flag)?;
+    args.remove(flag_pos);
+    if flag_pos >= args.len() {
+        eprintln!("{flag} requires a value");
+        std::process::exit(1);
+    }
+    Some(args.remove(flag_pos))
+}
+
+// Pulls "--record <spec>" out of the argument list in place and returns the
+// parsed output path/resolution if present.
+fn extract_record_flag(args: &mut Vec<String>) -> Option<(String, u32, u32)> {
+    extract_value_flag(args, "--record").map(|spec| parse_record_spec(&spec))
+}
+
+// Parses a numeric flag value, printing a clean error and exiting instead of
+// panicking on malformed CLI input.
+fn parse_flag_value<T: std::str::FromStr>(flag: &str, value: &str) -> T {
+    value.parse().unwrap_or_else(|_| {
+        eprintln!("{flag} must be a number, got {value:?}");
+        std::process::exit(1);
+    })
+}
+
+// "out.mp4" -> (out.mp4, DEFAULT_RECORD_WIDTH, DEFAULT_RECORD_HEIGHT)
+// "out.mp4:640x320" -> (out.mp4, 640, 320)
+fn parse_record_spec(spec: &str) -> (String, u32, u32) {
+    if let Some((path, res)) = spec.split_once(':') {
+        if let Some((w, h)) = res.split_once('x') {
+            if let (Ok(w), Ok(h)) = (w.parse(), h.parse()) {
+                return (path.to_string(), w, h);
+            }
+        }
+    }
+    (spec.to_string(), DEFAULT_RECORD_WIDTH, DEFAULT_RECORD_HEIGHT)
+}
 
 const MCYCLE: usize = 60;
 // Chip8 spec does not mention who quickly the system should actually run.
 // In general, 10 is a nice sweet spot.
 const TICKS_PER_FRAME: usize = 10; // 600Hz if M-Cycle is 60Hz
 
-fn draw_screen(emu: &Emu, canvas: &mut Canvas<Window>) {
-    // Clear canvas as black
-    canvas.set_draw_color(Color::RGB(0, 0, 0));
-    canvas.clear();
+const REWIND_CAPACITY: usize = 600;
+
+const DEFAULT_FG_COLOR: Color = Color::RGB(255, 255, 255);
+const DEFAULT_BG_COLOR: Color = Color::RGB(0, 0, 0);
+
+// Parses a "RRGGBB" hex triplet into a Color, falling back silently to the
+// CHIP-8 default monochrome palette when the string is malformed.
+fn parse_hex_color(s: &str, default: Color) -> Color {
+    if s.len() != 6 || !s.is_ascii() {
+        return default;
+    }
+    let channel = |range| u8::from_str_radix(&s[range], 16).ok();
+    match (channel(0..2), channel(2..4), channel(4..6)) {
+        (Some(r), Some(g), Some(b)) => Color::RGB(r, g, b),
+        _ => default,
+    }
+}
 
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum RunState {
+    Running,
+    Paused,
+}
+
+// Presentation is decoupled from the emulated 64x32 framebuffer: `Auto` fits
+// whatever window size SDL hands us (preserving the 2:1 aspect with
+// letterboxing), `Times` is a fixed integer/fractional multiple of the base
+// resolution, and `Fixed` is an explicit pixel size.
+#[derive(Clone, Copy)]
+enum ScaleMode {
+    Auto,
+    Times(f32),
+    Fixed(u32, u32),
+}
+
+impl ScaleMode {
+    // Accepts "auto", "<factor>x" (e.g. "3x", "2.5x") or "<w>x<h>" (e.g. "800x600").
+    fn parse(s: &str) -> Self {
+        if s.eq_ignore_ascii_case("auto") {
+            return ScaleMode::Auto;
+        }
+        if let Some(factor) = s
+            .strip_suffix('x')
+            .or_else(|| s.strip_suffix('X'))
+            .and_then(|f| f.parse::<f32>().ok())
+        {
+            return ScaleMode::Times(factor);
+        }
+        if let Some((w, h)) = s.split_once(['x', 'X']) {
+            if let (Ok(w), Ok(h)) = (w.parse::<u32>(), h.parse::<u32>()) {
+                return ScaleMode::Fixed(w, h);
+            }
+        }
+        ScaleMode::Times(DEFAULT_SCALE)
+    }
+
+    fn initial_window_size(&self) -> (u32, u32) {
+        match *self {
+            ScaleMode::Auto => ScaleMode::Times(DEFAULT_SCALE).initial_window_size(),
+            ScaleMode::Times(factor) => (
+                (SCREEN_WIDTH as f32 * factor) as u32,
+                (SCREEN_HEIGHT as f32 * factor) as u32,
+            ),
+            ScaleMode::Fixed(w, h) => (w, h),
+        }
+    }
+}
+
+// Computes the letterboxed destination rect for the CHIP-8 image, centered
+// inside the actual window size.
+fn compute_draw_rect(window_w: u32, window_h: u32, mode: ScaleMode) -> Rect {
+    let (draw_w, draw_h) = match mode {
+        ScaleMode::Auto => {
+            let scale = (window_w as f32 / SCREEN_WIDTH as f32)
+                .min(window_h as f32 / SCREEN_HEIGHT as f32)
+                .max(0.0);
+            (
+                (SCREEN_WIDTH as f32 * scale) as u32,
+                (SCREEN_HEIGHT as f32 * scale) as u32,
+            )
+        }
+        ScaleMode::Times(factor) => (
+            (SCREEN_WIDTH as f32 * factor) as u32,
+            (SCREEN_HEIGHT as f32 * factor) as u32,
+        ),
+        ScaleMode::Fixed(w, h) => (w, h),
+    };
+    let x = (window_w as i32 - draw_w as i32) / 2;
+    let y = (window_h as i32 - draw_h as i32) / 2;
+    Rect::new(x, y, draw_w, draw_h)
+}
+
+// Fixed-capacity ring buffer of Emu snapshots, one pushed per frame in which
+// the emulator advanced (running or single-stepped).
+// Rewinding just pops the most recent snapshot back into the live Emu.
+struct RewindBuffer {
+    snapshots: VecDeque<Emu>,
+    capacity: usize,
+}
+
+impl RewindBuffer {
+    fn new(capacity: usize) -> Self {
+        Self {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    fn push(&mut self, snapshot: Emu) {
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    fn pop(&mut self) -> Option<Emu> {
+        self.snapshots.pop_back()
+    }
+}
+
+const DEFAULT_TONE_FREQ: f32 = 440.0;
+const DEFAULT_TONE_VOLUME: f32 = 0.25;
+
+// Phase-continuous square wave: holding phase across callbacks (instead of
+// re-generating a fixed-length buffer per tick) is what keeps the waveform
+// click-free when the sound timer toggles on/off between callbacks.
+struct SquareWave {
+    phase_inc: f32,
+    phase: f32,
+    volume: f32,
+}
+
+impl AudioCallback for SquareWave {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        for x in out.iter_mut() {
+            *x = if self.phase <= 0.5 {
+                self.volume
+            } else {
+                -self.volume
+            };
+            self.phase = (self.phase + self.phase_inc) % 1.0;
+        }
+    }
+}
+
+// Resumes/pauses the device once per frame based on the sound timer, rather
+// than queuing a fresh buffer on every tick.
+fn play_audio(emu: &Emu, device: &AudioDevice<SquareWave>) {
+    if *emu.get_st() > 0 {
+        device.resume();
+    } else {
+        device.pause();
+    }
+}
+
+// Builds one RGB24 triplet per pixel of the 64x32 display; shared between
+// the screen texture and the optional frame recorder so both draw from the
+// exact same image.
+fn build_frame_rgb24(emu: &Emu, fg_color: Color, bg_color: Color) -> Vec<u8> {
     let screen_buf = emu.get_display();
-    // Now set draw color to white, iterate through each point and see if it should be drawn
-    canvas.set_draw_color(Color::RGB(255, 255, 255));
+    let mut frame = vec![0u8; SCREEN_WIDTH * SCREEN_HEIGHT * 3];
     for (i, pixel) in screen_buf.iter().enumerate() {
-        if *pixel {
-            // Convert our 1D array's index into a 2D (x, y) position
-            let x = (i % SCREEN_WIDTH) as u32;
-            let y = (i / SCREEN_WIDTH) as u32;
+        let color = if *pixel { fg_color } else { bg_color };
+        let offset = i * 3;
+        frame[offset] = color.r;
+        frame[offset + 1] = color.g;
+        frame[offset + 2] = color.b;
+    }
+    frame
+}
+
+// Uploads the frame into a streaming texture and lets SDL/GPU scale it onto
+// the canvas, instead of issuing up to 2048 fill_rect calls per frame.
+fn draw_screen(canvas: &mut Canvas<Window>, texture: &mut Texture, frame: &[u8], draw_rect: Rect) {
+    texture
+        .update(None, frame, SCREEN_WIDTH * 3)
+        .unwrap();
+
+    // Black bars outside draw_rect when the window doesn't match the 2:1 aspect.
+    canvas.set_draw_color(Color::RGB(0, 0, 0));
+    canvas.clear();
+    canvas.copy(texture, None, draw_rect).unwrap();
+}
+
+const DEBUG_PANEL_WIDTH: u32 = 260;
+const DEBUG_LINE_HEIGHT: i32 = 16;
+const DEBUG_DISASM_WINDOW: usize = 9;
+
+const BITMAP_FONT_PIXEL_SIZE: u32 = 2;
+
+// Renders the pause/step debug panel: pc, the decoded instruction window
+// around it, all v_reg, i_reg, sp, the stack, and both timers. Drawn as a
+// fixed-width strip docked to the left edge of the window, on top of the
+// scaled CHIP-8 picture.
+//
+// `font` is an optional user-supplied TTF (via --debug-font) for nicer text;
+// without one, falls back to the built-in bitmap font so the overlay works
+// out of the box.
+fn render_debug_overlay(
+    canvas: &mut Canvas<Window>,
+    texture_creator: &TextureCreator<WindowContext>,
+    font: Option<&Font>,
+    emu: &Emu,
+    window_height: u32,
+) {
+    // BlendMode::None (the canvas default) ignores alpha entirely, so the
+    // translucent panel color would otherwise render fully opaque.
+    let panel_rect = Rect::new(0, 0, DEBUG_PANEL_WIDTH, window_height);
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, 200));
+    canvas.fill_rect(panel_rect).unwrap();
+    canvas.set_blend_mode(BlendMode::None);
 
-            // Draw a rectangle at (x, y), scaled up by our SCALE value
-            let rect = Rect::new((x * SCALE) as i32, (y * SCALE) as i32, SCALE, SCALE);
-            canvas.fill_rect(rect).unwrap();
+    let mut lines = vec![
+        format!("PC: 0x{:03X}", emu.get_pc()),
+        format!("I:  0x{:03X}", emu.get_i_reg()),
+        format!("SP: {}", emu.get_sp()),
+        format!("DT: {}  ST: {}", emu.get_dt(), emu.get_st()),
+        String::new(),
+    ];
+    for (i, v) in emu.get_v_reg().iter().enumerate() {
+        lines.push(format!("V{:X}: 0x{:02X}", i, v));
+    }
+    lines.push(String::new());
+    lines.push("Stack:".to_string());
+    for (i, s) in emu.get_stack().iter().enumerate() {
+        lines.push(format!("[{}] 0x{:03X}", i, s));
+    }
+    lines.push(String::new());
+    lines.push("Disassembly:".to_string());
+    let window_start = emu.get_pc().saturating_sub((DEBUG_DISASM_WINDOW as u16 / 2) * 2);
+    for (addr, mnemonic) in emu.disassemble_range(window_start, DEBUG_DISASM_WINDOW) {
+        let marker = if addr == emu.get_pc() { '>' } else { ' ' };
+        lines.push(format!("{}0x{:03X}: {}", marker, addr, mnemonic));
+    }
+
+    let mut y = 4;
+    for line in lines {
+        if line.is_empty() {
+            y += DEBUG_LINE_HEIGHT;
+            continue;
         }
+        match font {
+            Some(font) => {
+                let surface = font
+                    .render(&line)
+                    .blended(Color::RGB(0, 255, 0))
+                    .unwrap();
+                let texture = texture_creator
+                    .create_texture_from_surface(&surface)
+                    .unwrap();
+                let TextureQuery { width, height, .. } = texture.query();
+                canvas
+                    .copy(&texture, None, Rect::new(4, y, width, height))
+                    .unwrap();
+            }
+            None => {
+                bitmap_font::draw_text(
+                    canvas,
+                    4,
+                    y,
+                    &line,
+                    Color::RGB(0, 255, 0),
+                    BITMAP_FONT_PIXEL_SIZE,
+                );
+            }
+        }
+        y += DEBUG_LINE_HEIGHT;
     }
-    canvas.present();
 }
 
 fn key2btn(key: Keycode) -> Option<usize> {
@@ -67,42 +436,54 @@ fn key2btn(key: Keycode) -> Option<usize> {
     }
 }
 
-// https://github.com/Rust-SDL2/rust-sdl2/blob/master/examples/audio-queue-squarewave.rs
-fn play_audio(emu: &Emu, audio_queue: &mut AudioQueue<i16>) {
-    if *emu.get_st() > 0 {
-        // Generate a square wave
-        let tone_volume = 1_000i16;
-        let period = 48_000 / 256;
-        let sample_count = 48_000 * 2; // 1s
-        let mut wav = Vec::new();
-
-        for x in 0..sample_count {
-            wav.push(if (x / period) % 2 == 0 {
-                tone_volume
-            } else {
-                -tone_volume
-            });
-        }
-        audio_queue.queue(&wav);
-    } else {
-        audio_queue.clear();
-    }
-}
-
 fn main() {
-    let args: Vec<_> = env::args().collect();
+    let mut args: Vec<_> = env::args().collect();
+    // Every option besides the rom path is a "--flag <value>" pair, pulled
+    // out before positional parsing so they can appear in any order.
+    let record_config = extract_record_flag(&mut args);
+    let tone_freq_arg = extract_value_flag(&mut args, "--tone-freq");
+    let tone_volume_arg = extract_value_flag(&mut args, "--tone-volume");
+    let fg_arg = extract_value_flag(&mut args, "--fg");
+    let bg_arg = extract_value_flag(&mut args, "--bg");
+    let scale_arg = extract_value_flag(&mut args, "--scale");
+    let controller_map_arg = extract_value_flag(&mut args, "--controller-map");
+    let debug_font_arg = extract_value_flag(&mut args, "--debug-font");
+
     if args.len() != 2 {
-        println!("Usage: cargo run path/to/game");
+        println!(
+            "Usage: cargo run path/to/game [--tone-freq HZ] [--tone-volume 0..1] [--fg RRGGBB] [--bg RRGGBB] [--scale auto|<factor>x|<w>x<h>] [--controller-map FILE] [--debug-font FILE] [--record <path>[:WxH]]"
+        );
         return;
     }
+    let tone_freq: f32 = tone_freq_arg
+        .map(|s| parse_flag_value("--tone-freq", &s))
+        .unwrap_or(DEFAULT_TONE_FREQ);
+    let tone_volume: f32 = tone_volume_arg
+        .map(|s| parse_flag_value("--tone-volume", &s))
+        .unwrap_or(DEFAULT_TONE_VOLUME);
+    let fg_color = fg_arg
+        .map(|s| parse_hex_color(&s, DEFAULT_FG_COLOR))
+        .unwrap_or(DEFAULT_FG_COLOR);
+    let bg_color = bg_arg
+        .map(|s| parse_hex_color(&s, DEFAULT_BG_COLOR))
+        .unwrap_or(DEFAULT_BG_COLOR);
+    let scale_mode = scale_arg
+        .map(|s| ScaleMode::parse(&s))
+        .unwrap_or(ScaleMode::Times(DEFAULT_SCALE));
+    let controller_mapping = controller_map_arg
+        .map(|path| load_controller_mapping(&path))
+        .unwrap_or_else(default_controller_mapping);
+    let debug_font_path = debug_font_arg;
 
     // Setup SDL
     let sdl_context = sdl2::init().unwrap();
     // video_subsystem
     let video_subsystem = sdl_context.video().unwrap();
+    let (mut window_width, mut window_height) = scale_mode.initial_window_size();
     let window = video_subsystem
-        .window("Chip-8 Emulator", WINDOW_WIDTH, WINDOW_HEIGHT)
+        .window("Chip-8 Emulator", window_width, window_height)
         .position_centered()
+        .resizable()
         .opengl()
         .build()
         .unwrap();
@@ -111,19 +492,44 @@ fn main() {
     canvas.clear();
     canvas.present();
 
+    let texture_creator = canvas.texture_creator();
+    let mut screen_texture = texture_creator
+        .create_texture_streaming(
+            PixelFormatEnum::RGB24,
+            SCREEN_WIDTH as u32,
+            SCREEN_HEIGHT as u32,
+        )
+        .unwrap();
+
     // audio_subsystem
     let audio_subsystem = sdl_context.audio().unwrap();
-    let mut audio_queue = audio_subsystem
-        .open_queue::<i16, _>(
+    let audio_device = audio_subsystem
+        .open_playback(
             None,
             &AudioSpecDesired {
-                freq: Some(48_000),
-                channels: Some(2),
+                freq: Some(44_100),
+                channels: Some(1),
                 samples: None, // default samples
             },
+            |spec| SquareWave {
+                phase_inc: tone_freq / spec.freq as f32,
+                phase: 0.0,
+                volume: tone_volume,
+            },
         )
         .unwrap();
-    audio_queue.resume();
+
+    // controller_subsystem: open the first attached gamepad, if any.
+    let game_controller_subsystem = sdl_context.game_controller().unwrap();
+    let _controller = (0..game_controller_subsystem.num_joysticks().unwrap_or(0))
+        .find(|&id| game_controller_subsystem.is_game_controller(id))
+        .and_then(|id| game_controller_subsystem.open(id).ok());
+
+    // Debug overlay text (toggled with Tab) falls back to the built-in
+    // bitmap font unless --debug-font points at a loadable TTF file.
+    let ttf_context = sdl2::ttf::init().unwrap();
+    let debug_font = debug_font_path.and_then(|path| ttf_context.load_font(path, 14).ok());
+    let mut show_debug_overlay = false;
 
     let mut event_pump = sdl_context.event_pump().unwrap();
 
@@ -134,43 +540,172 @@ fn main() {
     rom.read_to_end(&mut buffer).unwrap();
     chip8.load(&buffer);
 
+    let mut run_state = RunState::Running;
+    let mut rewinding = false;
+    let mut rewind_buffer = RewindBuffer::new(REWIND_CAPACITY);
+    // Tracks which D-pad button each analog axis is currently "holding down",
+    // so a motion back through the dead zone releases the right key.
+    let mut axis_state: HashMap<Axis, Button> = HashMap::new();
+
+    // A failed recording setup (bad codec, unwritable path) is reported and
+    // the session continues without recording rather than aborting the game.
+    let recorder = record_config.and_then(|(output_path, output_width, output_height)| {
+        match Recorder::start(RecorderConfig {
+            output_path,
+            capture_width: SCREEN_WIDTH as u32,
+            capture_height: SCREEN_HEIGHT as u32,
+            output_width,
+            output_height,
+        }) {
+            Ok(recorder) => Some(recorder),
+            Err(e) => {
+                eprintln!("recording disabled: {e}");
+                None
+            }
+        }
+    });
+
     let frame = Duration::from_millis((1000 / MCYCLE) as u64);
     'gameloop: loop {
         let now = Instant::now();
+        let mut step = false;
 
-        for _ in 0..TICKS_PER_FRAME {
-            for evt in event_pump.poll_iter() {
-                match evt {
-                    Event::Quit { .. } => {
-                        break 'gameloop;
+        for evt in event_pump.poll_iter() {
+            match evt {
+                Event::Quit { .. } => {
+                    break 'gameloop;
+                }
+                Event::Window {
+                    win_event: WindowEvent::Resized(w, h) | WindowEvent::SizeChanged(w, h),
+                    ..
+                } => {
+                    window_width = w as u32;
+                    window_height = h as u32;
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::Space),
+                    ..
+                } => {
+                    run_state = match run_state {
+                        RunState::Running => RunState::Paused,
+                        RunState::Paused => RunState::Running,
+                    };
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::N),
+                    ..
+                } => {
+                    if run_state == RunState::Paused {
+                        step = true;
                     }
-                    Event::KeyDown {
-                        keycode: Some(key), ..
-                    } => {
-                        if let Some(k) = key2btn(key) {
-                            chip8.keypress(k, true);
-                        }
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::Tab),
+                    ..
+                } => {
+                    show_debug_overlay = !show_debug_overlay;
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::Backspace),
+                    ..
+                } => {
+                    rewinding = true;
+                }
+                Event::KeyUp {
+                    keycode: Some(Keycode::Backspace),
+                    ..
+                } => {
+                    rewinding = false;
+                }
+                Event::KeyDown {
+                    keycode: Some(key), ..
+                } => {
+                    if let Some(k) = key2btn(key) {
+                        chip8.keypress(k, true);
                     }
-                    Event::KeyUp {
-                        keycode: Some(key), ..
-                    } => {
-                        if let Some(k) = key2btn(key) {
-                            chip8.keypress(k, false);
+                }
+                Event::KeyUp {
+                    keycode: Some(key), ..
+                } => {
+                    if let Some(k) = key2btn(key) {
+                        chip8.keypress(k, false);
+                    }
+                }
+                Event::ControllerButtonDown { button, .. } => {
+                    if let Some(&k) = controller_mapping.get(&button) {
+                        chip8.keypress(k, true);
+                    }
+                }
+                Event::ControllerButtonUp { button, .. } => {
+                    if let Some(&k) = controller_mapping.get(&button) {
+                        chip8.keypress(k, false);
+                    }
+                }
+                Event::ControllerAxisMotion { axis, value, .. } => {
+                    let new_button = axis_to_button(axis, value);
+                    if axis_state.get(&axis) != new_button.as_ref() {
+                        if let Some(old_button) = axis_state.remove(&axis) {
+                            if let Some(&k) = controller_mapping.get(&old_button) {
+                                chip8.keypress(k, false);
+                            }
+                        }
+                        if let Some(button) = new_button {
+                            if let Some(&k) = controller_mapping.get(&button) {
+                                chip8.keypress(k, true);
+                            }
+                            axis_state.insert(axis, button);
                         }
                     }
-                    _ => (),
                 }
+                _ => (),
             }
+        }
 
+        if rewinding {
+            if let Some(snapshot) = rewind_buffer.pop() {
+                chip8.restore(&snapshot);
+            }
+        } else if step {
             chip8.tick();
-            play_audio(&chip8, &mut audio_queue);
+            chip8.tick_timers();
+        } else if run_state == RunState::Running {
+            for _ in 0..TICKS_PER_FRAME {
+                chip8.tick();
+            }
+            chip8.tick_timers();
+        }
+
+        // Only snapshot frames where the emulator actually advanced, so a long
+        // pause doesn't flood the ring buffer with duplicates and evict real history.
+        if !rewinding && (step || run_state == RunState::Running) {
+            rewind_buffer.push(chip8.snapshot());
+        }
+
+        play_audio(&chip8, &audio_device);
+        let draw_rect = compute_draw_rect(window_width, window_height, scale_mode);
+        let frame_buf = build_frame_rgb24(&chip8, fg_color, bg_color);
+        draw_screen(&mut canvas, &mut screen_texture, &frame_buf, draw_rect);
+        if show_debug_overlay {
+            render_debug_overlay(
+                &mut canvas,
+                &texture_creator,
+                debug_font.as_ref(),
+                &chip8,
+                window_height,
+            );
         }
+        canvas.present();
 
-        chip8.tick_timers();
-        draw_screen(&chip8, &mut canvas);
+        if let Some(recorder) = &recorder {
+            recorder.push_frame(frame_buf);
+        }
 
         if let Some(remaining) = frame.checked_sub(now.elapsed()) {
             sleep(remaining);
         }
     }
+
+    if let Some(recorder) = recorder {
+        recorder.finish();
+    }
 }
@@ -0,0 +1,195 @@
+// Optional session recording: captures the RGB24 framebuffer produced for
+// the screen texture each display frame and hands it off to an ffmpeg
+// encoder running on a background thread, so encoding never stalls the
+// emulation loop.
+
+use std::sync::mpsc::{self, SyncSender};
+use std::thread::JoinHandle;
+
+use ffmpeg_next as ffmpeg;
+use ffmpeg::format::Pixel;
+use ffmpeg::software::scaling::{context::Context as ScalingContext, flag::Flags as ScalingFlags};
+
+enum Message {
+    Frame(Vec<u8>),
+    Finish,
+}
+
+pub struct RecorderConfig {
+    pub output_path: String,
+    pub capture_width: u32,
+    pub capture_height: u32,
+    pub output_width: u32,
+    pub output_height: u32,
+}
+
+const DISPLAY_FPS: i32 = 60;
+
+// How many frames can queue up for the encoder thread before `push_frame`
+// starts dropping them. Bounded so a slow encoder sheds load instead of
+// growing memory without limit.
+const FRAME_QUEUE_CAPACITY: usize = 8;
+
+// Picks the encoder/pixel format from the output file's extension: `.gif`
+// goes through the palettized GIF encoder, anything else is encoded as H.264
+// in the container ffmpeg picks for the path (e.g. MP4 for `.mp4`).
+fn codec_for_path(path: &str) -> (ffmpeg::codec::Id, Pixel) {
+    if path.to_ascii_lowercase().ends_with(".gif") {
+        (ffmpeg::codec::Id::Gif, Pixel::PAL8)
+    } else {
+        (ffmpeg::codec::Id::H264, Pixel::YUV420P)
+    }
+}
+
+// Holds everything needed to push one scaled, encoded frame at a time; built
+// up-front by `Recorder::start` so a bad codec/path is reported to the
+// caller instead of silently killing the background thread.
+struct EncodingSession {
+    output: ffmpeg::format::context::Output,
+    encoder: ffmpeg::encoder::Video,
+    scaler: ScalingContext,
+    stream_index: usize,
+    capture_width: u32,
+    capture_height: u32,
+    output_width: u32,
+    output_height: u32,
+    output_format: Pixel,
+    pts: i64,
+}
+
+impl EncodingSession {
+    fn open(config: &RecorderConfig) -> Result<Self, ffmpeg::Error> {
+        let (codec_id, output_format) = codec_for_path(&config.output_path);
+
+        let mut output = ffmpeg::format::output(&config.output_path)?;
+        let codec = ffmpeg::encoder::find(codec_id).ok_or(ffmpeg::Error::EncoderNotFound)?;
+        let mut stream = output.add_stream(codec)?;
+        let stream_index = stream.index();
+
+        let mut encoder = ffmpeg::codec::context::Context::new_with_codec(codec)
+            .encoder()
+            .video()?;
+        encoder.set_width(config.output_width);
+        encoder.set_height(config.output_height);
+        encoder.set_format(output_format);
+        encoder.set_time_base(ffmpeg::Rational(1, DISPLAY_FPS));
+        encoder.set_frame_rate(Some(ffmpeg::Rational(DISPLAY_FPS, 1)));
+
+        let encoder = encoder.open_as(codec)?;
+        stream.set_parameters(&encoder);
+
+        output.write_header()?;
+
+        let scaler = ScalingContext::get(
+            Pixel::RGB24,
+            config.capture_width,
+            config.capture_height,
+            output_format,
+            config.output_width,
+            config.output_height,
+            ScalingFlags::BILINEAR,
+        )?;
+
+        Ok(Self {
+            output,
+            encoder,
+            scaler,
+            stream_index,
+            capture_width: config.capture_width,
+            capture_height: config.capture_height,
+            output_width: config.output_width,
+            output_height: config.output_height,
+            output_format,
+            pts: 0,
+        })
+    }
+
+    fn encode_frame(&mut self, rgb_frame: Vec<u8>) -> Result<(), ffmpeg::Error> {
+        let mut src =
+            ffmpeg::util::frame::Video::new(Pixel::RGB24, self.capture_width, self.capture_height);
+        src.data_mut(0)[..rgb_frame.len()].copy_from_slice(&rgb_frame);
+
+        let mut dst =
+            ffmpeg::util::frame::Video::new(self.output_format, self.output_width, self.output_height);
+        self.scaler.run(&src, &mut dst)?;
+        dst.set_pts(Some(self.pts));
+        self.pts += 1;
+
+        self.encoder.send_frame(&dst)?;
+        self.drain_packets()
+    }
+
+    fn finish(mut self) -> Result<(), ffmpeg::Error> {
+        self.encoder.send_eof()?;
+        self.drain_packets()?;
+        self.output.write_trailer()
+    }
+
+    fn drain_packets(&mut self) -> Result<(), ffmpeg::Error> {
+        let mut packet = ffmpeg::Packet::empty();
+        while self.encoder.receive_packet(&mut packet).is_ok() {
+            packet.set_stream(self.stream_index);
+            packet.write_interleaved(&mut self.output)?;
+        }
+        Ok(())
+    }
+}
+
+// Owns the channel into the encoder thread; `finish` blocks until the file
+// is flushed and finalized.
+pub struct Recorder {
+    sender: SyncSender<Message>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Recorder {
+    // Performed synchronously so a bad path or missing codec is reported to
+    // the caller up front, rather than silently killing a detached thread.
+    pub fn start(config: RecorderConfig) -> Result<Self, String> {
+        ffmpeg::init().map_err(|e| format!("failed to initialize ffmpeg: {e}"))?;
+        let mut session = EncodingSession::open(&config).map_err(|e| {
+            format!(
+                "failed to start recording to {}: {e}",
+                config.output_path
+            )
+        })?;
+
+        let (sender, receiver) = mpsc::sync_channel::<Message>(FRAME_QUEUE_CAPACITY);
+        let output_path = config.output_path;
+        let handle = std::thread::spawn(move || {
+            for message in receiver {
+                let rgb_frame = match message {
+                    Message::Frame(bytes) => bytes,
+                    Message::Finish => break,
+                };
+                if let Err(e) = session.encode_frame(rgb_frame) {
+                    eprintln!("recording: failed to encode frame for {output_path}: {e}");
+                }
+            }
+            if let Err(e) = session.finish() {
+                eprintln!("recording: failed to finalize {output_path}: {e}");
+            }
+        });
+
+        Ok(Self {
+            sender,
+            handle: Some(handle),
+        })
+    }
+
+    // Never blocks the emulation loop: if the encoder thread is behind and
+    // the bounded queue is full (or the receiver is gone), the frame is
+    // dropped rather than backing up the game loop.
+    pub fn push_frame(&self, frame: Vec<u8>) {
+        let _ = self.sender.try_send(Message::Frame(frame));
+    }
+
+    pub fn finish(mut self) {
+        // Blocking send: unlike a dropped frame, the Finish marker must reach
+        // the encoder thread so the file gets flushed and finalized.
+        let _ = self.sender.send(Message::Finish);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}